@@ -1,21 +1,165 @@
 //! A simple, lock-free, single-producer single-consumer channel that can hold a single item.
 //! Unlike crates like [triple_buffer][https://crates.io/crates/triple_buffer], the content is moved between ends of the channel instead of borrowed.
+//!
+//! This crate is `no_std` (it only needs `alloc`, for the `Shared`/`Arc` handle each half
+//! holds). Without the `sync` feature it uses `Cell`/`Rc` and skips atomic fences entirely,
+//! for single-threaded, embedded use; with it (the default), it uses real atomics and `Arc`
+//! so the two halves can be sent across threads. Thread-blocking operations (`send`/`recv`
+//! and their `_timeout` variants) additionally require the `std` feature, since they rely on
+//! OS thread parking.
 
-use std::{
-    cell::Cell,
+#![no_std]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use core::{
+    cell::{Cell, UnsafeCell},
+    future::Future,
     mem::MaybeUninit,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::sync::{AtomicBool, AtomicU8, Ordering, Shared as Arc};
+
+#[cfg(feature = "std")]
+use alloc::sync::Arc as ThreadWakerArc;
+#[cfg(feature = "std")]
+use std::{
+    task::Wake,
+    thread::{self, Thread},
+    time::{Duration, Instant},
 };
 
+pub mod queue;
+#[cfg(feature = "std")]
+pub mod select;
+pub mod sync;
 pub mod triple;
 
+const WAKER_EMPTY: u8 = 0;
+const WAKER_REGISTERING: u8 = 1;
+const WAKER_WAITING: u8 = 2;
+
+/// A single-slot waker registration, following the standard three-state
+/// `AtomicWaker` dance (EMPTY/REGISTERING/WAITING) so that a waker registered
+/// concurrently with a wake-up is never lost.
+struct WakerSlot {
+    state: AtomicU8,
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+// SAFETY: access to `waker` is gated by `state`, which only ever permits one
+// side (the registrant or the waker) to touch the cell at a time.
+unsafe impl Send for WakerSlot {}
+unsafe impl Sync for WakerSlot {}
+
+impl WakerSlot {
+    fn new() -> Self {
+        WakerSlot {
+            state: AtomicU8::new(WAKER_EMPTY),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    /// Register interest in being woken. Callers must re-check their
+    /// condition *after* calling this to close the lost-wakeup window.
+    fn register(&self, waker: &Waker) {
+        // Both halves register on a trapdoor's one waker slot (the writer from `send`, the
+        // reader from `recv`, the futures and `select`), and `wake` can fire concurrently
+        // at any time, so we always have to CAS our way into REGISTERING from EMPTY or
+        // WAITING before touching the cell.
+        loop {
+            let claimed = self
+                .state
+                .compare_exchange(WAKER_EMPTY, WAKER_REGISTERING, Ordering::Acquire, Ordering::Acquire)
+                .is_ok()
+                || self
+                    .state
+                    .compare_exchange(WAKER_WAITING, WAKER_REGISTERING, Ordering::Acquire, Ordering::Acquire)
+                    .is_ok();
+
+            if !claimed {
+                // Another registration is in flight; spin until the slot settles back into a
+                // state we can claim.
+                core::hint::spin_loop();
+                continue;
+            }
+
+            // SAFETY: we hold the REGISTERING state, so we're the only one touching the cell.
+            unsafe { *self.waker.get() = Some(waker.clone()) };
+
+            match self.state.compare_exchange(
+                WAKER_REGISTERING,
+                WAKER_WAITING,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {}
+                // A wake raced us while we were registering; take it back out and fire it now.
+                Err(_) => {
+                    let waker = unsafe { (*self.waker.get()).take() };
+                    self.state.store(WAKER_EMPTY, Ordering::Release);
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+            }
+
+            return;
+        }
+    }
+
+    /// Wake whoever is currently registered, if anyone.
+    fn wake(&self) {
+        if self.state.swap(WAKER_EMPTY, Ordering::AcqRel) == WAKER_WAITING {
+            let waker = unsafe { (*self.waker.get()).take() };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Drop any registration without waking it, e.g. because the registrant moved on
+    /// after being woken by a different door in a [`select`][crate::select] group.
+    #[cfg(feature = "std")]
+    fn deregister(&self) {
+        if self.state.swap(WAKER_EMPTY, Ordering::AcqRel) == WAKER_WAITING {
+            unsafe { (*self.waker.get()).take() };
+        }
+    }
+}
+
+/// Adapts a parked thread into a [`Waker`] so that blocking `send`/`recv` can drive the same
+/// [`WakerSlot`] registration used by the async futures, instead of duplicating the
+/// empty/full-wait logic.
+#[cfg(feature = "std")]
+struct ThreadWaker(Thread);
+
+#[cfg(feature = "std")]
+impl Wake for ThreadWaker {
+    fn wake(self: ThreadWakerArc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &ThreadWakerArc<Self>) {
+        self.0.unpark();
+    }
+}
+
+#[cfg(feature = "std")]
+fn thread_waker() -> Waker {
+    Waker::from(ThreadWakerArc::new(ThreadWaker(thread::current())))
+}
+
 /// An unsplit trapdoor.
 pub struct Trapdoor<T> {
     populated: AtomicBool,
     data: Cell<MaybeUninit<T>>,
+    waker: WakerSlot,
 }
 
 impl<T> Trapdoor<T> {
@@ -31,6 +175,7 @@ impl<T> Trapdoor<T> {
         Trapdoor {
             populated: AtomicBool::new(false),
             data: Cell::new(MaybeUninit::uninit()),
+            waker: WakerSlot::new(),
         }
     }
 
@@ -46,6 +191,7 @@ impl<T> Trapdoor<T> {
         Trapdoor {
             populated: AtomicBool::new(true),
             data: Cell::new(MaybeUninit::new(value)),
+            waker: WakerSlot::new(),
         }
     }
 
@@ -65,8 +211,10 @@ impl<T> Trapdoor<T> {
     ///
     /// # Examples
     ///
-    /// If `T` implements Send, the two trapdoor halves will as well.
-    /// ```
+    /// If `T` implements Send, the two trapdoor halves will as well (this requires the `sync`
+    /// feature, since without it the halves are built on `Rc`, which is never `Send`).
+    #[cfg_attr(not(feature = "sync"), doc = "```ignore")]
+    #[cfg_attr(feature = "sync", doc = "```")]
     /// # use std::thread;
     /// # use trapdoor::Trapdoor;
     /// let (tx, mut rx) = Trapdoor::with_value(()).split();
@@ -98,6 +246,7 @@ impl<T> Trapdoor<T> {
         } else {
             self.data.set(MaybeUninit::new(value));
             self.populated.store(true, Ordering::Release);
+            self.waker.wake();
 
             Ok(())
         }
@@ -112,6 +261,7 @@ impl<T> Trapdoor<T> {
             let value = self.data.replace(MaybeUninit::uninit()).assume_init();
 
             self.populated.store(false, Ordering::Release);
+            self.waker.wake();
 
             Some(value)
         } else {
@@ -128,8 +278,17 @@ impl<T> Trapdoor<T> {
     }
 }
 
+impl<T> Default for Trapdoor<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Cell isn't Sync because of interior mutability,
-/// but we guarantee no concurrent mutation.
+/// but we guarantee no concurrent mutation. Only needed (and only sound, since the
+/// single-threaded backend's `Cell`-based atomics aren't actually synchronized) under
+/// the `sync` feature.
+#[cfg(feature = "sync")]
 unsafe impl<T> Sync for Trapdoor<T> where T: Send {}
 
 impl<T> Drop for Trapdoor<T> {
@@ -159,7 +318,7 @@ impl<T> TrapdoorWrite<T> {
     /// tx.store(123);
     /// ```
     pub fn store(&mut self, value: T) {
-        if let Err(_) = self.try_store(value) {
+        if self.try_store(value).is_err() {
             panic!("Trapdoor is already occupied");
         }
     }
@@ -178,6 +337,65 @@ impl<T> TrapdoorWrite<T> {
         // Only one TrapdoorWrite may exist for any TrapdoorInner, so we're the only writer
         unsafe { self.0.try_store(value) }
     }
+
+    /// Blocks the calling thread until the trapdoor is empty, then stores `value`.
+    /// # Examples
+    /// ```
+    /// # use std::thread;
+    /// # use trapdoor::Trapdoor;
+    /// let (mut tx, mut rx) = Trapdoor::new().split();
+    /// tx.send(123);
+    /// let handle = thread::spawn(move || rx.recv());
+    /// tx.send(456); // blocks until the spawned thread takes 123
+    /// assert_eq!(handle.join().unwrap(), 123);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn send(&mut self, mut value: T) {
+        loop {
+            match self.try_store(value) {
+                Ok(()) => return,
+                Err(v) => value = v,
+            }
+
+            let waker = thread_waker();
+            self.0.waker.register(&waker);
+
+            match self.try_store(value) {
+                Ok(()) => return,
+                Err(v) => value = v,
+            }
+
+            thread::park();
+        }
+    }
+
+    /// Like [`send`][Self::send], but gives up and returns `value` as an error if the trapdoor
+    /// doesn't empty out within `timeout`.
+    #[cfg(feature = "std")]
+    pub fn send_timeout(&mut self, mut value: T, timeout: Duration) -> Result<(), T> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            match self.try_store(value) {
+                Ok(()) => return Ok(()),
+                Err(v) => value = v,
+            }
+
+            let waker = thread_waker();
+            self.0.waker.register(&waker);
+
+            match self.try_store(value) {
+                Ok(()) => return Ok(()),
+                Err(v) => value = v,
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(value);
+            }
+            thread::park_timeout(deadline - now);
+        }
+    }
 }
 
 pub struct TrapdoorRead<T>(Arc<Trapdoor<T>>);
@@ -210,6 +428,60 @@ impl<T> TrapdoorRead<T> {
         unsafe { self.0.try_take() }
     }
 
+    /// Blocks the calling thread until the trapdoor holds a value, then takes it.
+    /// # Examples
+    /// ```
+    /// # use std::thread;
+    /// # use trapdoor::Trapdoor;
+    /// let (mut tx, mut rx) = Trapdoor::new().split();
+    /// let handle = thread::spawn(move || rx.recv());
+    /// tx.store(123);
+    /// assert_eq!(handle.join().unwrap(), 123);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn recv(&mut self) -> T {
+        loop {
+            if let Some(value) = self.try_take() {
+                return value;
+            }
+
+            let waker = thread_waker();
+            self.0.waker.register(&waker);
+
+            if let Some(value) = self.try_take() {
+                return value;
+            }
+
+            thread::park();
+        }
+    }
+
+    /// Like [`recv`][Self::recv], but gives up and returns `None` if no value arrives within
+    /// `timeout`.
+    #[cfg(feature = "std")]
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Option<T> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if let Some(value) = self.try_take() {
+                return Some(value);
+            }
+
+            let waker = thread_waker();
+            self.0.waker.register(&waker);
+
+            if let Some(value) = self.try_take() {
+                return Some(value);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            thread::park_timeout(deadline - now);
+        }
+    }
+
     pub fn peek(&self) -> &T {
         self.try_peek().expect("Trapdoor is not occupied")
     }
@@ -217,4 +489,84 @@ impl<T> TrapdoorRead<T> {
     pub fn try_peek(&self) -> Option<&T> {
         unsafe { self.0.try_peek() }
     }
+
+    /// Registers interest in this door's waker slot, e.g. from [`select`][crate::select].
+    #[cfg(feature = "std")]
+    pub(crate) fn register_waker(&self, waker: &Waker) {
+        self.0.waker.register(waker);
+    }
+
+    /// Clears any registration made via [`register_waker`][Self::register_waker] without
+    /// waking it.
+    #[cfg(feature = "std")]
+    pub(crate) fn deregister_waker(&self) {
+        self.0.waker.deregister();
+    }
+
+    /// Returns a future that resolves to the next value stored into the trapdoor.
+    /// # Examples
+    /// ```ignore
+    /// # use trapdoor::Trapdoor;
+    /// let (mut tx, mut rx) = Trapdoor::new().split();
+    /// tx.store(123);
+    /// assert_eq!(rx.recv_async().await, 123);
+    /// ```
+    pub fn recv_async(&mut self) -> RecvAsync<'_, T> {
+        RecvAsync(self)
+    }
+
+    /// Returns a future that resolves to a reference to the next value stored into the trapdoor,
+    /// without taking it out.
+    pub fn recv_ref_async(&mut self) -> RecvRefAsync<'_, T> {
+        RecvRefAsync(self)
+    }
+}
+
+/// A future returned by [`TrapdoorRead::recv_async`].
+pub struct RecvAsync<'a, T>(&'a mut TrapdoorRead<T>);
+
+impl<'a, T> Future for RecvAsync<'a, T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let this = self.get_mut();
+
+        if let Some(value) = this.0.try_take() {
+            return Poll::Ready(value);
+        }
+
+        this.0 .0.waker.register(cx.waker());
+
+        // Re-check after registering to close the lost-wakeup window: the writer may have
+        // stored (and woken a by-then-unregistered waker) in between our first check and
+        // registering above.
+        match this.0.try_take() {
+            Some(value) => Poll::Ready(value),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// A future returned by [`TrapdoorRead::recv_ref_async`].
+pub struct RecvRefAsync<'a, T>(&'a mut TrapdoorRead<T>);
+
+impl<'a, T> Future for RecvRefAsync<'a, T> {
+    type Output = &'a T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<&'a T> {
+        let this = self.get_mut();
+
+        // SAFETY: the reference is backed by the `Arc<Trapdoor<T>>` shared by `this.0`, which
+        // outlives `'a`; the trapdoor is single-reader, so no other call can invalidate it.
+        if let Some(value) = this.0.try_peek() {
+            return Poll::Ready(unsafe { core::mem::transmute::<&T, &'a T>(value) });
+        }
+
+        this.0 .0.waker.register(cx.waker());
+
+        match this.0.try_peek() {
+            Some(value) => Poll::Ready(unsafe { core::mem::transmute::<&T, &'a T>(value) }),
+            None => Poll::Pending,
+        }
+    }
 }
@@ -0,0 +1,151 @@
+//! A bounded, lock-free, single-producer single-consumer ring buffer.
+//! Unlike [`crate::Trapdoor`], which holds exactly one item and forces a strict
+//! handshake between the two ends, a `Queue` lets the producer get `N` items ahead
+//! of the consumer before pushing fails. Neither half blocks: a full queue makes
+//! `push` panic and `try_push` return the value, and an empty one makes `pop` panic
+//! and `try_pop` return `None`.
+
+use alloc::boxed::Box;
+use core::{cell::Cell, mem::MaybeUninit};
+
+use crate::sync::{AtomicUsize, Ordering, Shared as Arc};
+
+/// Pads `T` out to a cache line so that `head` and `tail`, which are written by
+/// different threads, don't false-share.
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+/// An unsplit queue with a fixed capacity of `N` items.
+pub struct Queue<T, const N: usize> {
+    buffer: Box<[Cell<MaybeUninit<T>>]>,
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+}
+
+impl<T, const N: usize> Queue<T, N> {
+    /// Construct a new, empty queue with capacity for `N` items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use trapdoor::queue::Queue;
+    /// let queue: Queue<u32, 16> = Queue::new();
+    /// ```
+    pub fn new() -> Self {
+        Queue {
+            buffer: (0..N)
+                .map(|_| Cell::new(MaybeUninit::uninit()))
+                .collect(),
+            head: CachePadded(AtomicUsize::new(0)),
+            tail: CachePadded(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Split the queue into the producer and consumer halves.
+    pub fn split(self) -> (QueueWrite<T, N>, QueueRead<T, N>) {
+        let arc = Arc::new(self);
+
+        (QueueWrite(arc.clone()), QueueRead(arc))
+    }
+
+    /// Attempt to push a value into the queue.
+    /// Fails and returns the value if the queue is full.
+    /// SAFETY: you must be the only producer
+    unsafe fn try_push(&self, value: T) -> Result<(), T> {
+        let tail = self.tail.0.load(Ordering::Relaxed);
+        let head = self.head.0.load(Ordering::Acquire);
+
+        if tail - head >= N {
+            return Err(value);
+        }
+
+        self.buffer[tail % N].set(MaybeUninit::new(value));
+        self.tail.0.store(tail + 1, Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Attempt to pop a value from the queue.
+    /// Returns `None` if the queue is empty.
+    /// SAFETY: you must be the only consumer
+    unsafe fn try_pop(&self) -> Option<T> {
+        let head = self.head.0.load(Ordering::Relaxed);
+        let tail = self.tail.0.load(Ordering::Acquire);
+
+        if head >= tail {
+            return None;
+        }
+
+        // Safe - slots between `head` and `tail` are always populated
+        let value = self.buffer[head % N]
+            .replace(MaybeUninit::uninit())
+            .assume_init();
+        self.head.0.store(head + 1, Ordering::Release);
+
+        Some(value)
+    }
+}
+
+impl<T, const N: usize> Default for Queue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cell isn't Sync because of interior mutability, but we guarantee no concurrent
+/// mutation of any one slot. Only needed/sound under the `sync` feature, see
+/// [`Trapdoor`][crate::Trapdoor]'s equivalent impl.
+#[cfg(feature = "sync")]
+unsafe impl<T, const N: usize> Sync for Queue<T, N> where T: Send {}
+
+impl<T, const N: usize> Drop for Queue<T, N> {
+    fn drop(&mut self) {
+        // Safe - at this point, we are the only consumer
+        unsafe {
+            // Drop any values still buffered between `head` and `tail`
+            while self.try_pop().is_some() {}
+        }
+    }
+}
+
+/// The "producer" half of a queue.
+pub struct QueueWrite<T, const N: usize>(Arc<Queue<T, N>>);
+
+impl<T, const N: usize> QueueWrite<T, N> {
+    /// Pushes a value into the queue.
+    /// # Panics
+    /// If the queue is already full, this function will panic.
+    /// If this is not desired, use the [try_push][Self::try_push] function instead.
+    pub fn push(&mut self, value: T) {
+        if self.try_push(value).is_err() {
+            panic!("Queue is full");
+        }
+    }
+
+    /// Attempts to push a value into the queue.
+    ///
+    /// If the queue is full, `value` will be returned as the error.
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        // Only one QueueWrite may exist for any Queue, so we're the only producer
+        unsafe { self.0.try_push(value) }
+    }
+}
+
+/// The "consumer" half of a queue.
+pub struct QueueRead<T, const N: usize>(Arc<Queue<T, N>>);
+
+impl<T, const N: usize> QueueRead<T, N> {
+    /// Pops the next value from the queue.
+    /// # Panics
+    /// If the queue is empty, this function will panic.
+    /// If this is not desired, use the [try_pop][Self::try_pop] function instead.
+    pub fn pop(&mut self) -> T {
+        self.try_pop().expect("Queue is empty")
+    }
+
+    /// Attempts to pop the next value from the queue. Returns `None` if the queue is empty.
+    pub fn try_pop(&mut self) -> Option<T> {
+        // Only one QueueRead may exist for any Queue, so we're the only consumer
+        unsafe { self.0.try_pop() }
+    }
+}
@@ -0,0 +1,66 @@
+//! `select!`-style blocking across several trapdoors of the same item type, so a
+//! consumer can wait for whichever one fills up first instead of picking one to
+//! block on up front.
+
+use alloc::vec::Vec;
+use std::thread;
+
+use crate::{thread_waker, TrapdoorRead};
+
+/// Waits on multiple [`TrapdoorRead`] handles at once.
+pub struct Select<'a, T> {
+    readers: Vec<&'a mut TrapdoorRead<T>>,
+}
+
+impl<'a, T> Select<'a, T> {
+    /// Build a select group over the given doors.
+    pub fn new(readers: Vec<&'a mut TrapdoorRead<T>>) -> Self {
+        Select { readers }
+    }
+
+    /// Blocks until any registered door holds a value, and returns its index (without
+    /// taking the value out, so the caller can inspect it further before committing
+    /// to [`TrapdoorRead::take`]).
+    pub fn ready(&mut self) -> usize {
+        loop {
+            if let Some(index) = self.poll() {
+                return index;
+            }
+
+            let waker = thread_waker();
+            for reader in &self.readers {
+                reader.register_waker(&waker);
+            }
+
+            if let Some(index) = self.poll() {
+                // We're about to stop waiting: drop the registration we just installed on
+                // every other door, so a later `store` there doesn't spuriously unpark us.
+                self.deregister_all_but(index);
+                return index;
+            }
+
+            thread::park();
+            self.deregister_all_but(usize::MAX);
+        }
+    }
+
+    /// Blocks until any registered door holds a value, then takes and returns it
+    /// alongside the index of the door it came from.
+    pub fn recv(&mut self) -> (usize, T) {
+        let index = self.ready();
+        let value = self.readers[index].take();
+        (index, value)
+    }
+
+    fn poll(&self) -> Option<usize> {
+        self.readers.iter().position(|reader| reader.try_peek().is_some())
+    }
+
+    fn deregister_all_but(&self, index: usize) {
+        for (i, reader) in self.readers.iter().enumerate() {
+            if i != index {
+                reader.deregister_waker();
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Internal synchronization abstraction, in the spirit of rustc's own `sync` module:
+//! under `feature = "sync"`, [`AtomicBool`], [`AtomicU8`], [`AtomicUsize`] and [`Shared`]
+//! are real atomics and `Arc`, so the crate can be shared across threads; without it, they
+//! collapse to `Cell` and `Rc`, skipping atomic fences entirely for single-threaded,
+//! embedded `no_std` users.
+//!
+//! Both configurations expose the same surface, named after `core::sync::atomic`'s:
+//! `load`/`store`/`swap`/`compare_exchange`/`get_mut` on every type, `fetch_add` on
+//! `AtomicU8` and `AtomicUsize`, and `fetch_sub` on `AtomicU8` only, so the rest of the
+//! crate doesn't need to know which one it's built against.
+
+#[cfg(feature = "sync")]
+mod imp {
+    pub use alloc::sync::Arc as Shared;
+    pub use core::sync::atomic::Ordering;
+
+    macro_rules! atomic {
+        ($name:ident, $inner:ty, $value:ty) => {
+            #[derive(Debug, Default)]
+            pub struct $name($inner);
+
+            impl $name {
+                pub const fn new(value: $value) -> Self {
+                    Self(<$inner>::new(value))
+                }
+
+                pub fn load(&self, order: Ordering) -> $value {
+                    self.0.load(order)
+                }
+
+                pub fn store(&self, value: $value, order: Ordering) {
+                    self.0.store(value, order)
+                }
+
+                pub fn swap(&self, value: $value, order: Ordering) -> $value {
+                    self.0.swap(value, order)
+                }
+
+                pub fn compare_exchange(
+                    &self,
+                    current: $value,
+                    new: $value,
+                    success: Ordering,
+                    failure: Ordering,
+                ) -> Result<$value, $value> {
+                    self.0.compare_exchange(current, new, success, failure)
+                }
+
+                pub fn get_mut(&mut self) -> &mut $value {
+                    self.0.get_mut()
+                }
+            }
+        };
+    }
+
+    atomic!(AtomicBool, core::sync::atomic::AtomicBool, bool);
+    atomic!(AtomicU8, core::sync::atomic::AtomicU8, u8);
+    atomic!(AtomicUsize, core::sync::atomic::AtomicUsize, usize);
+
+    impl AtomicU8 {
+        pub fn fetch_add(&self, value: u8, order: Ordering) -> u8 {
+            self.0.fetch_add(value, order)
+        }
+
+        pub fn fetch_sub(&self, value: u8, order: Ordering) -> u8 {
+            self.0.fetch_sub(value, order)
+        }
+    }
+
+    impl AtomicUsize {
+        pub fn fetch_add(&self, value: usize, order: Ordering) -> usize {
+            self.0.fetch_add(value, order)
+        }
+    }
+}
+
+#[cfg(not(feature = "sync"))]
+mod imp {
+    use core::cell::Cell;
+
+    pub use alloc::rc::Rc as Shared;
+
+    /// Stands in for `core::sync::atomic::Ordering` so callers don't need to
+    /// `cfg`-gate the argument they pass; it's simply ignored.
+    #[derive(Debug, Clone, Copy)]
+    pub enum Ordering {
+        Relaxed,
+        Acquire,
+        Release,
+        AcqRel,
+        SeqCst,
+    }
+
+    macro_rules! atomic {
+        ($name:ident, $value:ty) => {
+            #[derive(Debug, Default)]
+            pub struct $name(Cell<$value>);
+
+            impl $name {
+                pub const fn new(value: $value) -> Self {
+                    Self(Cell::new(value))
+                }
+
+                pub fn load(&self, _order: Ordering) -> $value {
+                    self.0.get()
+                }
+
+                pub fn store(&self, value: $value, _order: Ordering) {
+                    self.0.set(value)
+                }
+
+                pub fn swap(&self, value: $value, _order: Ordering) -> $value {
+                    self.0.replace(value)
+                }
+
+                pub fn compare_exchange(
+                    &self,
+                    current: $value,
+                    new: $value,
+                    _success: Ordering,
+                    _failure: Ordering,
+                ) -> Result<$value, $value> {
+                    let existing = self.0.get();
+                    if existing == current {
+                        self.0.set(new);
+                        Ok(existing)
+                    } else {
+                        Err(existing)
+                    }
+                }
+
+                pub fn get_mut(&mut self) -> &mut $value {
+                    self.0.get_mut()
+                }
+            }
+        };
+    }
+
+    atomic!(AtomicBool, bool);
+    atomic!(AtomicU8, u8);
+    atomic!(AtomicUsize, usize);
+
+    impl AtomicU8 {
+        pub fn fetch_add(&self, value: u8, _order: Ordering) -> u8 {
+            let old = self.0.get();
+            self.0.set(old.wrapping_add(value));
+            old
+        }
+
+        pub fn fetch_sub(&self, value: u8, _order: Ordering) -> u8 {
+            let old = self.0.get();
+            self.0.set(old.wrapping_sub(value));
+            old
+        }
+    }
+
+    impl AtomicUsize {
+        pub fn fetch_add(&self, value: usize, _order: Ordering) -> usize {
+            let old = self.0.get();
+            self.0.set(old.wrapping_add(value));
+            old
+        }
+    }
+}
+
+pub use imp::*;
@@ -1,29 +1,20 @@
-use atomic::Atomic;
-use std::{
-    cell::RefCell,
-    mem::{ManuallyDrop, MaybeUninit},
-    ops::{Deref, DerefMut},
-    sync::{atomic::Ordering, Arc},
-};
-
-// Present this enum as a single u16 for the purposes of atomic operations
-// That way both of the fields must be updated simultaneously
-#[repr(C, align(2))]
-#[derive(Clone, Copy)]
-struct BucketInfo {
-    acquired: u8,
-    canonical: u8,
-}
+use core::{cell::UnsafeCell, mem::MaybeUninit, ops::Deref};
 
-static_assertions::const_assert!(Atomic::<BucketInfo>::is_lock_free());
+use crate::sync::{AtomicU8, Ordering, Shared as Arc};
 
 /// an unsplit MontyHall
 pub struct MontyHall<T> {
-    buckets: [RefCell<MaybeUninit<T>>; 3],
-    bucket_info: Atomic<BucketInfo>,
+    buckets: [UnsafeCell<MaybeUninit<T>>; 3],
+    /// The bucket that `load` should hand out next.
+    canonical: AtomicU8,
+    /// How many live `MontyHallHandle`s currently point at each bucket.
+    refcounts: [AtomicU8; 3],
 }
 
-/// A reference to the element stored in the MontyHall at a certain point in time
+/// A reference to the element stored in the MontyHall at a certain point in time.
+/// Multiple handles, possibly from different threads, may point at the same bucket
+/// at once; the bucket's value is only ever dropped once the last handle pointing
+/// at it goes away *and* a newer value has been published as canonical.
 pub struct MontyHallHandle<'a, T> {
     acquired: usize,
     monte_hall: &'a MontyHall<T>,
@@ -33,48 +24,21 @@ impl<'a, T> Deref for MontyHallHandle<'a, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        unsafe { &*self.monte_hall.buckets[self.acquired].borrow().as_ptr() }
-    }
-}
-
-impl<'a, T> DerefMut for MontyHallHandle<'a, T> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe {
-            &mut *self.monte_hall.buckets[self.acquired]
-                .borrow_mut()
-                .as_mut_ptr()
-        }
+        unsafe { &*self.monte_hall.buckets[self.acquired].get().cast::<T>() }
     }
 }
 
 impl<'a, T> Drop for MontyHallHandle<'a, T> {
     fn drop(&mut self) {
-        // Take ownership of the value, and drop it if we turned out to be holding a non-canonical reference
-        let value = ManuallyDrop::new(unsafe {
-            self.monte_hall.buckets[self.acquired]
-                .replace(MaybeUninit::uninit())
-                .assume_init()
-        });
-
-        let old_info = self
-            .monte_hall
-            .bucket_info
-            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |bucket_info| {
-                Some(BucketInfo {
-                    acquired: u8::MAX,
-                    ..bucket_info
-                })
-            })
-            .or(Err(()))
-            .unwrap();
-
-        // We were holding onto a non-canonical handle, so drop it
-        if old_info.acquired != old_info.canonical {
-            println!("Dropped non-canonical value {}", old_info.acquired);
-            ManuallyDrop::into_inner(value);
+        // If we were the last handle pointing at this bucket, and the writer has since
+        // moved on to a different canonical bucket, the value is now unreachable: drop it.
+        if self.monte_hall.refcounts[self.acquired].fetch_sub(1, Ordering::AcqRel) == 1
+            && self.monte_hall.canonical.load(Ordering::Acquire) as usize != self.acquired
+        {
+            unsafe {
+                (*self.monte_hall.buckets[self.acquired].get()).assume_init_drop();
+            }
         }
-
-        // Otherwise, the existing value continues to live
     }
 }
 
@@ -83,14 +47,12 @@ impl<T> MontyHall<T> {
     pub fn new(value: T) -> Self {
         MontyHall {
             buckets: [
-                RefCell::new(MaybeUninit::new(value)),
-                RefCell::new(MaybeUninit::uninit()),
-                RefCell::new(MaybeUninit::uninit()),
+                UnsafeCell::new(MaybeUninit::new(value)),
+                UnsafeCell::new(MaybeUninit::uninit()),
+                UnsafeCell::new(MaybeUninit::uninit()),
             ],
-            bucket_info: Atomic::new(BucketInfo {
-                acquired: u8::MAX,
-                canonical: 0,
-            }),
+            canonical: AtomicU8::new(0),
+            refcounts: [AtomicU8::new(0), AtomicU8::new(0), AtomicU8::new(0)],
         }
     }
 
@@ -109,91 +71,77 @@ impl<T> MontyHall<T> {
         (MontyHallWrite(arc.clone()), MontyHallRead(arc))
     }
 
-    /// Load the value from the MontyHall
-    /// SAFETY: you must be the only reader
-    pub(self) unsafe fn load(&self) -> MontyHallHandle<'_, T> {
-        let BucketInfo { canonical, .. } = self
-            .bucket_info
-            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |bucket_info| {
-                Some(BucketInfo {
-                    acquired: bucket_info.canonical,
-                    ..bucket_info
-                })
-            })
-            .or(Err(()))
-            .unwrap();
-
-        MontyHallHandle {
-            acquired: canonical as usize,
-            monte_hall: self,
+    /// Acquire a handle to the canonical value
+    /// SAFETY: may be called from any number of concurrent readers
+    unsafe fn load(&self) -> MontyHallHandle<'_, T> {
+        loop {
+            let canonical = self.canonical.load(Ordering::Acquire) as usize;
+            self.refcounts[canonical].fetch_add(1, Ordering::AcqRel);
+
+            // The writer may have moved `canonical` on (and possibly dropped our bucket's
+            // value, if its refcount had reached zero) between our load and our fetch_add
+            // above; retry against the now-current bucket if so.
+            if self.canonical.load(Ordering::Acquire) as usize == canonical {
+                return MontyHallHandle {
+                    acquired: canonical,
+                    monte_hall: self,
+                };
+            }
+
+            self.refcounts[canonical].fetch_sub(1, Ordering::AcqRel);
         }
     }
 
     /// Store a value into the MontyHall
     /// SAFETY: you must be the only writer
-    pub(self) unsafe fn store(&self, value: T) {
+    unsafe fn store(&self, value: T) {
         let write_bucket = self.write_bucket();
 
-        self.buckets[write_bucket].replace(MaybeUninit::new(value));
-
-        let BucketInfo {
-            acquired,
-            canonical,
-        } = self
-            .bucket_info
-            .fetch_update(Ordering::AcqRel, Ordering::Acquire, move |bucket_info| {
-                Some(BucketInfo {
-                    canonical: write_bucket as u8,
-                    ..bucket_info
-                })
-            })
-            .or(Err(()))
-            .unwrap();
-
-        println!(
-            "Replaced {} {} with {} {}",
-            acquired, canonical, acquired, write_bucket
-        );
-
-        // If we did not have an active handle to the old canonical element, drop it
-        if acquired != canonical {
-            println!("Dropped old value {}", canonical);
-
-            self.buckets[canonical as usize]
-                .replace(MaybeUninit::uninit())
-                .assume_init();
+        *self.buckets[write_bucket].get() = MaybeUninit::new(value);
+
+        let old_canonical = self.canonical.swap(write_bucket as u8, Ordering::AcqRel) as usize;
+
+        // If nobody holds a handle to the value we just replaced, drop it now; otherwise the
+        // last `MontyHallHandle` pointing at it will drop it when it goes away.
+        if self.refcounts[old_canonical].load(Ordering::Acquire) == 0 {
+            (*self.buckets[old_canonical].get()).assume_init_drop();
         }
     }
 
-    /// Get the bucket we can always write to
+    /// Get a bucket we can always write to: one that isn't canonical and has no live handles.
+    /// With only 3 buckets, both non-canonical ones can be pinned by readers at once; in that
+    /// (rare, transient) case, spin until one of them drops its last handle rather than give up.
     /// Unsafe because it must only be invoked from the writer
     unsafe fn write_bucket(&self) -> usize {
-        let BucketInfo {
-            acquired,
-            canonical,
-        } = self.bucket_info.load(Ordering::Relaxed);
-        let mut mask = 0b111u32;
-
-        if acquired < 3 {
-            mask &= !(1 << acquired as usize);
-        }
+        loop {
+            let canonical = self.canonical.load(Ordering::Acquire) as usize;
 
-        mask &= !(1 << canonical as usize);
+            if let Some(bucket) =
+                (0..3).find(|&i| i != canonical && self.refcounts[i].load(Ordering::Acquire) == 0)
+            {
+                return bucket;
+            }
 
-        // Obtain the index of the lowest free bucket
-        mask.trailing_zeros() as usize
+            core::hint::spin_loop();
+        }
     }
 }
 
+/// `UnsafeCell` isn't Sync, but we guarantee exclusive access to a bucket's contents
+/// between the single writer and however many readers currently hold its refcount up.
+/// Only needed/sound under the `sync` feature, see [`Trapdoor`][crate::Trapdoor]'s
+/// equivalent impl.
+#[cfg(feature = "sync")]
+unsafe impl<T> Sync for MontyHall<T> where T: Send + Sync {}
+
 impl<T> Drop for MontyHall<T> {
     fn drop(&mut self) {
-        // It's impossible to end up here with a non-u8::MAX value for acquired, so we only need to drop canonical
-        let BucketInfo { canonical, .. } = self.bucket_info.load(Ordering::Relaxed);
+        // It's impossible to end up here with any outstanding handles, so we only need to
+        // drop the canonical bucket.
+        let canonical = *self.canonical.get_mut() as usize;
 
         unsafe {
-            self.buckets[canonical as usize]
-                .replace(MaybeUninit::uninit())
-                .assume_init();
+            self.buckets[canonical].get_mut().assume_init_drop();
         }
     }
 }
@@ -210,12 +158,21 @@ impl<T> MontyHallWrite<T> {
     }
 }
 
-/// The "receiving" half of a MontyHall
+/// The "receiving" half of a MontyHall.
+///
+/// Unlike `TrapdoorRead`, this half may be freely cloned: every clone can concurrently
+/// `load()` the latest published value.
 pub struct MontyHallRead<T>(Arc<MontyHall<T>>);
 
+impl<T> Clone for MontyHallRead<T> {
+    fn clone(&self) -> Self {
+        MontyHallRead(self.0.clone())
+    }
+}
+
 impl<T> MontyHallRead<T> {
     /// Load the value in the MontyHall.
-    pub fn load(&mut self) -> MontyHallHandle<'_, T> {
+    pub fn load(&self) -> MontyHallHandle<'_, T> {
         unsafe { self.0.load() }
     }
 }
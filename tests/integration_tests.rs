@@ -50,15 +50,13 @@ fn test_store_take() {
     let (mut tx, mut rx) = Trapdoor::new().split();
 
     tx.store(now);
-    match tx.try_store(now) {
-        Ok(_) => panic!("Double store"),
-        _ => {}
+    if tx.try_store(now).is_ok() {
+        panic!("Double store");
     }
 
     assert_eq!(rx.take(), now);
-    match rx.try_take() {
-        Some(_) => panic!("Double take"),
-        _ => {}
+    if rx.try_take().is_some() {
+        panic!("Double take");
     }
 }
 
@@ -69,7 +67,7 @@ mod triple {
 
     #[test]
     fn test_store_load() {
-        let (mut tx, mut rx) = MontyHall::new(1).split();
+        let (mut tx, rx) = MontyHall::new(1).split();
 
         let handle = rx.load();
         assert_eq!(*handle, 1);
@@ -106,4 +104,248 @@ mod triple {
 
         assert!(did_drop);
     }
+
+    #[test]
+    /// Ensures multiple cloned read halves can hold live handles to the same value at once
+    fn test_multiple_readers() {
+        let (mut tx, rx) = MontyHall::new(1).split();
+        let rx2 = rx.clone();
+
+        let handle = rx.load();
+        let handle2 = rx2.load();
+        assert_eq!(*handle, 1);
+        assert_eq!(*handle2, 1);
+
+        tx.store(2);
+        assert_eq!(*handle, 1);
+        assert_eq!(*handle2, 1);
+
+        std::mem::drop(handle);
+        std::mem::drop(handle2);
+
+        assert_eq!(*rx.load(), 2);
+    }
+}
+
+mod queue {
+    use trapdoor::queue::Queue;
+
+    use crate::DropObserver;
+
+    #[test]
+    fn test_push_pop() {
+        let (mut tx, mut rx) = Queue::<u32, 4>::new().split();
+
+        tx.push(1);
+        tx.push(2);
+        tx.push(3);
+
+        assert_eq!(rx.pop(), 1);
+        assert_eq!(rx.pop(), 2);
+
+        tx.push(4);
+        tx.push(5);
+
+        assert_eq!(rx.pop(), 3);
+        assert_eq!(rx.pop(), 4);
+        assert_eq!(rx.pop(), 5);
+        assert_eq!(rx.try_pop(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_push_full() {
+        let (mut tx, _rx) = Queue::<u32, 2>::new().split();
+
+        tx.push(1);
+        tx.push(2);
+        tx.push(3);
+    }
+
+    #[test]
+    fn test_drop() {
+        let mut did_drop_first = false;
+        let mut did_drop_second = false;
+        let (mut tx, rx) = Queue::<_, 4>::new().split();
+
+        tx.push(DropObserver(&mut did_drop_first));
+        tx.push(DropObserver(&mut did_drop_second));
+
+        std::mem::drop(tx);
+        std::mem::drop(rx);
+
+        assert!(did_drop_first);
+        assert!(did_drop_second);
+    }
+}
+
+#[cfg(feature = "std")]
+mod select {
+    use trapdoor::select::Select;
+    use trapdoor::Trapdoor;
+
+    #[test]
+    fn test_ready_index() {
+        let (mut tx1, mut rx1) = Trapdoor::new().split();
+        let (mut tx2, mut rx2) = Trapdoor::new().split();
+
+        tx2.store(123);
+
+        let mut select = Select::new(vec![&mut rx1, &mut rx2]);
+        let (index, value) = select.recv();
+
+        assert_eq!(index, 1);
+        assert_eq!(value, 123);
+
+        tx1.store(456);
+        assert_eq!(rx1.take(), 456);
+    }
+}
+
+#[cfg(feature = "std")]
+mod blocking {
+    use std::{thread, time::Duration};
+
+    use trapdoor::Trapdoor;
+
+    #[test]
+    /// `recv` actually parks until a value is stored from a different thread, instead of
+    /// busy-waiting or returning early.
+    fn test_recv_blocks_until_store() {
+        let (mut tx, mut rx) = Trapdoor::new().split();
+
+        let handle = thread::spawn(move || rx.recv());
+        thread::sleep(Duration::from_millis(50));
+        tx.store(123);
+
+        assert_eq!(handle.join().unwrap(), 123);
+    }
+
+    #[test]
+    /// `send` parks until the trapdoor is emptied out by a different thread.
+    fn test_send_blocks_until_take() {
+        let (mut tx, mut rx) = Trapdoor::new().split();
+
+        tx.store(1);
+        let handle = thread::spawn(move || tx.send(2));
+        thread::sleep(Duration::from_millis(50));
+
+        assert_eq!(rx.take(), 1);
+        handle.join().unwrap();
+        assert_eq!(rx.take(), 2);
+    }
+
+    #[test]
+    /// `recv_timeout` returns the value as soon as it arrives, without waiting out the
+    /// full timeout.
+    fn test_recv_timeout_completes_before_deadline() {
+        let (mut tx, mut rx) = Trapdoor::new().split();
+
+        let handle = thread::spawn(move || rx.recv_timeout(Duration::from_secs(10)));
+        thread::sleep(Duration::from_millis(50));
+        tx.store(123);
+
+        assert_eq!(handle.join().unwrap(), Some(123));
+    }
+
+    #[test]
+    /// `recv_timeout` gives up and returns `None` if nothing arrives in time.
+    fn test_recv_timeout_expires() {
+        let (_tx, mut rx) = Trapdoor::<u32>::new().split();
+
+        assert_eq!(rx.recv_timeout(Duration::from_millis(50)), None);
+    }
+
+    #[test]
+    /// `send_timeout` returns `Ok` as soon as the trapdoor is emptied out, without waiting
+    /// out the full timeout.
+    fn test_send_timeout_completes_before_deadline() {
+        let (mut tx, mut rx) = Trapdoor::new().split();
+
+        tx.store(1);
+        let handle = thread::spawn(move || tx.send_timeout(2, Duration::from_secs(10)));
+        thread::sleep(Duration::from_millis(50));
+
+        assert_eq!(rx.take(), 1);
+        assert_eq!(handle.join().unwrap(), Ok(()));
+        assert_eq!(rx.take(), 2);
+    }
+
+    #[test]
+    /// `send_timeout` gives up and hands the value back as an error if the trapdoor never
+    /// empties out in time.
+    fn test_send_timeout_expires() {
+        let (mut tx, _rx) = Trapdoor::new().split();
+
+        tx.store(1);
+        assert_eq!(tx.send_timeout(2, Duration::from_millis(50)), Err(2));
+    }
+}
+
+#[cfg(feature = "sync")]
+mod recv_async {
+    use std::{
+        future::Future,
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+        thread,
+        time::Duration,
+    };
+
+    use trapdoor::Trapdoor;
+
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// A minimal single-future executor, just enough to drive `recv_async` to completion.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(value) => return value,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    /// `recv_async` actually suspends (rather than spinning) until a value is stored from
+    /// another thread, and wakes back up once it is.
+    fn test_recv_async_blocks_until_store() {
+        let (mut tx, mut rx) = Trapdoor::new().split();
+
+        let handle = thread::spawn(move || block_on(rx.recv_async()));
+        thread::sleep(Duration::from_millis(50));
+        tx.store(123);
+
+        assert_eq!(handle.join().unwrap(), 123);
+    }
+
+    #[test]
+    /// `recv_ref_async` also suspends until a value is stored from another thread, and
+    /// leaves the value in the trapdoor afterward since it only peeks rather than takes.
+    fn test_recv_ref_async_blocks_until_store_and_leaves_value() {
+        let (mut tx, mut rx) = Trapdoor::new().split();
+
+        let handle = thread::spawn(move || {
+            let value = *block_on(rx.recv_ref_async());
+            (value, *rx.peek())
+        });
+        thread::sleep(Duration::from_millis(50));
+        tx.store(123);
+
+        assert_eq!(handle.join().unwrap(), (123, 123));
+    }
 }